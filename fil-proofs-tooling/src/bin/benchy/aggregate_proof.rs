@@ -0,0 +1,164 @@
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use filecoin_proofs::{aggregate_seal_commit_proofs, verify_aggregate_seal_commit_proofs, with_shape};
+use log::info;
+use serde::Serialize;
+use serde_json::json;
+use storage_proofs_core::api_version::ApiVersion;
+
+use crate::metrics::BenchReport;
+use crate::shared::{porep_config, replicate, sector_id, SEED};
+
+/// The only `--arity` value the real aggregation API can honor: it picks
+/// its own internal recursion structure and exposes no knob for `benchy`
+/// to drive from the outside.
+const UNCONTROLLABLE_ARITY: usize = 2;
+
+/// A single `num_agg` point in the sweep: how long aggregation and
+/// verification took at that count, and how big the resulting aggregate
+/// proof is.
+#[derive(Debug, Serialize)]
+struct SweepPoint {
+    num_agg: usize,
+    aggregation_ms: u64,
+    verification_ms: u64,
+    amortized_verification_ms: f64,
+    proof_bytes: usize,
+}
+
+/// Benchmarks aggregation of seal (PoRep) commit proofs for a sector of
+/// `sector_size` bytes, via the real `aggregate_seal_commit_proofs`/
+/// `verify_aggregate_seal_commit_proofs` API. Sweeps `num_agg` across
+/// powers of two up to the requested count. One real sector is sealed
+/// up front; for each point this aggregates `num_agg` copies of its commit proof (each
+/// paired with the same sealing seed `replicate` used, standing in for
+/// `num_agg` different sectors sealed identically), and times aggregation,
+/// batched verification, and the amortized per-proof verification cost
+/// (verification time / num_agg), so maintainers can see how aggregation
+/// cost scales.
+///
+/// Unlike the old placeholder, the real aggregation circuit picks its own
+/// internal recursion structure rather than exposing one `benchy` can
+/// drive from the outside, so `arity` no longer controls a benchy-side
+/// recursion loop — since there's nothing left for it to do, a value other
+/// than the default is rejected outright rather than silently ignored.
+pub fn run(sector_size: usize, num_agg: usize, arity: usize) -> Result<BenchReport> {
+    if arity != UNCONTROLLABLE_ARITY {
+        bail!(
+            "aggregate-proof: --arity has no effect on the real aggregation API, which picks its own \
+             recursion structure; pass --arity {} (the default) or omit the flag",
+            UNCONTROLLABLE_ARITY
+        );
+    }
+    let api_version = ApiVersion::V1_1_0;
+
+    info!(
+        "aggregate-proof: sector_size={} num_agg={} arity={}",
+        sector_size, num_agg, arity
+    );
+
+    let mut report = BenchReport::new("aggregate-proof", sector_size, api_version.to_string());
+    let mut sweep = Vec::new();
+
+    let config = porep_config(sector_size, api_version)?;
+    let sector = replicate(sector_size, api_version, sector_id(0))?;
+
+    for n in sweep_values(num_agg) {
+        // Every slot reuses the same sealed sector, so it must also reuse
+        // the seed that sector was actually sealed against — the seed is
+        // baked into the commit proof's public inputs, and an aggregate
+        // whose seed array doesn't match what each proof was generated
+        // with will simply fail to verify.
+        let comm_rs: Vec<[u8; 32]> = (0..n).map(|_| sector.comm_r).collect();
+        let seeds: Vec<[u8; 32]> = (0..n).map(|_| SEED).collect();
+        let commit_outputs: Vec<Vec<u8>> = (0..n).map(|_| sector.seal_proof.clone()).collect();
+
+        let agg_start = Instant::now();
+        let aggregate = with_shape!(
+            sector_size as u64,
+            aggregate_level,
+            config,
+            &comm_rs,
+            &seeds,
+            &commit_outputs
+        )?;
+        let aggregation = agg_start.elapsed();
+
+        let verify_start = Instant::now();
+        let valid = with_shape!(
+            sector_size as u64,
+            verify_level,
+            config,
+            aggregate.clone(),
+            &comm_rs,
+            &seeds,
+            &commit_outputs
+        )?;
+        let verification = verify_start.elapsed();
+        let amortized_ms = verification.as_secs_f64() * 1000.0 / n as f64;
+
+        info!(
+            "aggregate-proof: n={} aggregation={:?} verification={:?} amortized={:.4}ms/proof proof_bytes={}",
+            n,
+            aggregation,
+            verification,
+            amortized_ms,
+            aggregate.len()
+        );
+
+        report.record_phase(format!("aggregation[n={}]", n), aggregation);
+        report.record_phase(format!("verification[n={}]", n), verification);
+        report.pass &= valid;
+
+        if n == num_agg {
+            report.proof_bytes = aggregate.len();
+        }
+
+        sweep.push(SweepPoint {
+            num_agg: n,
+            aggregation_ms: aggregation.as_millis() as u64,
+            verification_ms: verification.as_millis() as u64,
+            amortized_verification_ms: amortized_ms,
+            proof_bytes: aggregate.len(),
+        });
+    }
+
+    std::fs::remove_dir_all(&sector.cache_dir)?;
+
+    report.set_extra(json!({ "arity": arity, "sweep": sweep }));
+    report.finish();
+    Ok(report)
+}
+
+/// Powers of two from 2 below `num_agg`, followed by `num_agg` itself
+/// (or 1 if `num_agg` is 0), whether or not it is a power of two.
+fn sweep_values(num_agg: usize) -> Vec<usize> {
+    let mut values = Vec::new();
+    let mut n = 2;
+    while n < num_agg {
+        values.push(n);
+        n *= 2;
+    }
+    values.push(num_agg.max(1));
+    values
+}
+
+fn aggregate_level<Tree: 'static + storage_proofs_core::merkle::MerkleTreeTrait>(
+    config: filecoin_proofs::types::PoRepConfig,
+    comm_rs: &[[u8; 32]],
+    seeds: &[[u8; 32]],
+    commit_outputs: &[Vec<u8>],
+) -> Result<Vec<u8>> {
+    aggregate_seal_commit_proofs::<Tree>(config, comm_rs, seeds, commit_outputs).map_err(Into::into)
+}
+
+fn verify_level<Tree: 'static + storage_proofs_core::merkle::MerkleTreeTrait>(
+    config: filecoin_proofs::types::PoRepConfig,
+    aggregate: Vec<u8>,
+    comm_rs: &[[u8; 32]],
+    seeds: &[[u8; 32]],
+    commit_outputs: &[Vec<u8>],
+) -> Result<bool> {
+    verify_aggregate_seal_commit_proofs::<Tree>(config, aggregate, comm_rs, seeds, commit_outputs).map_err(Into::into)
+}
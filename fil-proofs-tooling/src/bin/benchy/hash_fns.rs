@@ -0,0 +1,33 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use log::info;
+use sha2::{Digest, Sha256};
+
+/// Runs a fixed number of hash invocations and logs the elapsed wall-clock time.
+///
+/// This is a quick and dirty way to get a feel for the raw cost of the hash
+/// function used inside the proving circuits, without paying for circuit
+/// synthesis.
+pub fn run() -> Result<()> {
+    const SAMPLES: usize = 100_000;
+
+    let mut input = [0u8; 64];
+    let start = Instant::now();
+
+    for i in 0..SAMPLES {
+        input[0] = i as u8;
+        let digest = Sha256::digest(&input);
+        input[1] = digest[0];
+    }
+
+    let elapsed = start.elapsed();
+    info!(
+        "hash-constraints: {} samples in {:?} ({:?}/sample)",
+        SAMPLES,
+        elapsed,
+        elapsed / SAMPLES as u32
+    );
+
+    Ok(())
+}
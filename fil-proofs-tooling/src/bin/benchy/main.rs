@@ -1,7 +1,7 @@
 //requires nightly, or later stable version
 //#![warn(clippy::unwrap_used)]
 
-use std::io::{stdin, stdout};
+use std::io::stdin;
 use std::str::FromStr;
 
 use anyhow::Result;
@@ -10,14 +10,18 @@ use clap::{value_t, App, AppSettings, Arg, SubCommand};
 
 use storage_proofs_core::api_version::ApiVersion;
 
+use crate::metrics::MetricsFormat;
 use crate::prodbench::ProdbenchInputs;
 
+mod aggregate_proof;
 mod hash_fns;
 mod merkleproofs;
+mod metrics;
 mod prodbench;
+mod seal;
+mod shared;
 mod window_post;
 mod winning_post;
-mod aggregate_proof;
 
 fn main() -> Result<()> {
     fil_logger::init();
@@ -74,6 +78,21 @@ fn main() -> Result<()> {
                 .default_value("")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("checkpoint-dir")
+                .long("checkpoint-dir")
+                .required(false)
+                .help("Directory to persist versioned, per-phase checkpoint chunks in")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("resume-from")
+                .long("resume-from")
+                .required(false)
+                .help("Resume after the named checkpoint chunk instead of auto-detecting")
+                .requires("checkpoint-dir")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("size")
                 .long("size")
@@ -187,19 +206,96 @@ fn main() -> Result<()> {
                 .required(true)
                 .help("The data size (e.g. 2KiB)")
                 .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("arity")
+                .long("arity")
+                .required(false)
+                .default_value("2")
+                .help(
+                    "Must be 2 (the default); the aggregation API picks its own recursion structure, \
+                     so any other value is rejected",
+                )
+                .takes_value(true),
+        );
+
+    let seal_cmd = SubCommand::with_name("seal")
+        .about("Benchmark phase-granular PoRep seal")
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .required(true)
+                .help("The data size (e.g. 2KiB)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("api_version")
+                .long("api-version")
+                .required(true)
+                .help("The api_version to use (default: 1.0.0)")
+                .default_value("1.0.0")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("cache")
+                .long("cache")
+                .required(false)
+                .help("The directory where cached phase outputs are persisted")
+                .default_value("")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("phases")
+                .long("phases")
+                .required(false)
+                .default_value("")
+                .help(
+                    "Comma-separated subset of phases to run (add-piece, precommit-phase1, \
+                     precommit-phase2, commit-phase1, commit-phase2, verify); default is all",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("reuse-cache")
+                .long("reuse-cache")
+                .required(false)
+                .help("Reuse prior phase outputs in --cache instead of starting from a clean cache")
+                .takes_value(false),
         );
 
     let matches = App::new("benchy")
         .setting(AppSettings::ArgRequiredElseHelp)
         .version("0.1")
+        .arg(
+            Arg::with_name("metrics-format")
+                .long("metrics-format")
+                .global(true)
+                .required(false)
+                .default_value("json")
+                .possible_values(&["json", "csv"])
+                .help("Format used to report benchmark metrics")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("metrics-out")
+                .long("metrics-out")
+                .global(true)
+                .required(false)
+                .help("Path to write benchmark metrics to (default: stdout)")
+                .takes_value(true),
+        )
         .subcommand(window_post_cmd)
         .subcommand(winning_post_cmd)
         .subcommand(hash_cmd)
         .subcommand(prodbench_cmd)
         .subcommand(merkleproof_cmd)
         .subcommand(agg_proof_cmd)
+        .subcommand(seal_cmd)
         .get_matches();
 
+    let metrics_format = value_t!(matches, "metrics-format", MetricsFormat)?;
+    let metrics_out = matches.value_of("metrics-out").map(|s| s.to_string());
+
     match matches.subcommand() {
         ("window-post", Some(m)) => {
             let preserve_cache = m.is_present("preserve-cache");
@@ -210,9 +306,11 @@ fn main() -> Result<()> {
             let skip_commit_phase2 = m.is_present("skip-commit-phase2");
             let test_resume = m.is_present("test-resume");
             let cache_dir = value_t!(m, "cache", String)?;
+            let checkpoint_dir = m.value_of("checkpoint-dir").map(|s| s.to_string());
+            let resume_from = m.value_of("resume-from").map(|s| s.to_string());
             let sector_size = Byte::from_str(value_t!(m, "size", String)?)?.get_bytes() as usize;
             let api_version = ApiVersion::from_str(&value_t!(m, "api_version", String)?)?;
-            window_post::run(
+            let report = window_post::run(
                 sector_size,
                 api_version,
                 cache_dir,
@@ -222,12 +320,16 @@ fn main() -> Result<()> {
                 skip_commit_phase1,
                 skip_commit_phase2,
                 test_resume,
+                checkpoint_dir,
+                resume_from,
             )?;
+            metrics::write_report(&report, metrics_format, metrics_out.as_deref())?;
         }
         ("winning-post", Some(m)) => {
             let sector_size = Byte::from_str(value_t!(m, "size", String)?)?.get_bytes() as usize;
             let api_version = ApiVersion::from_str(&value_t!(m, "api_version", String)?)?;
-            winning_post::run(sector_size, api_version)?;
+            let report = winning_post::run(sector_size, api_version)?;
+            metrics::write_report(&report, metrics_format, metrics_out.as_deref())?;
         }
         ("hash-constraints", Some(_m)) => {
             hash_fns::run()?;
@@ -236,7 +338,8 @@ fn main() -> Result<()> {
             let size = Byte::from_str(value_t!(m, "size", String)?)?.get_bytes() as usize;
 
             let proofs = value_t!(m, "proofs", usize)?;
-            merkleproofs::run(size, proofs, m.is_present("validate"))?;
+            let report = merkleproofs::run(size, proofs, m.is_present("validate"))?;
+            metrics::write_report(&report, metrics_format, metrics_out.as_deref())?;
         }
         ("prodbench", Some(m)) => {
             let inputs: ProdbenchInputs = if m.is_present("config") {
@@ -250,6 +353,7 @@ fn main() -> Result<()> {
             }
             .expect("failed to deserialize stdin to ProdbenchInputs");
 
+            let api_version = inputs.api_version.clone();
             let outputs = prodbench::run(
                 inputs,
                 m.is_present("skip-seal-proof"),
@@ -258,13 +362,24 @@ fn main() -> Result<()> {
                 m.is_present("only-add-piece"),
             );
 
-            serde_json::to_writer(stdout(), &outputs)
-                .expect("failed to write ProdbenchOutput to stdout")
+            let report = prodbench::to_bench_report(&outputs, api_version);
+            metrics::write_report(&report, metrics_format, metrics_out.as_deref())?;
         }
         ("aggregate-proof", Some(m)) => {
             let nums = Byte::from_str(value_t!(m, "num_agg", String)?)?.get_bytes() as usize;
             let sector_size = Byte::from_str(value_t!(m, "size", String)?)?.get_bytes() as usize;
-            aggregate_proof::run(sector_size, nums)?;
+            let arity = value_t!(m, "arity", usize)?;
+            let report = aggregate_proof::run(sector_size, nums, arity)?;
+            metrics::write_report(&report, metrics_format, metrics_out.as_deref())?;
+        }
+        ("seal", Some(m)) => {
+            let sector_size = Byte::from_str(value_t!(m, "size", String)?)?.get_bytes() as usize;
+            let api_version = ApiVersion::from_str(&value_t!(m, "api_version", String)?)?;
+            let cache_dir = value_t!(m, "cache", String)?;
+            let phases = seal::parse_phases(&value_t!(m, "phases", String)?)?;
+            let reuse_cache = m.is_present("reuse-cache");
+            let report = seal::run(sector_size, api_version, &phases, cache_dir, reuse_cache)?;
+            metrics::write_report(&report, metrics_format, metrics_out.as_deref())?;
         }
         _ => unreachable!(),
     }
@@ -0,0 +1,62 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use log::info;
+use merkletree::merkle::MerkleTree;
+use merkletree::store::VecStore;
+use rand::{thread_rng, Rng};
+use storage_proofs_core::hasher::{Domain, Hasher, PoseidonHasher};
+
+use crate::metrics::BenchReport;
+
+type TreeHasher = PoseidonHasher;
+type TreeDomain = <TreeHasher as Hasher>::Domain;
+type TreeFunction = <TreeHasher as Hasher>::Function;
+
+/// Builds a real Poseidon Merkle tree — the hasher `storage-proofs` uses
+/// for its commitments — over `size` bytes worth of leaves, generates
+/// `proofs` inclusion proofs at random leaf indexes, and (optionally)
+/// validates each one, logging how long generation and validation took.
+pub fn run(size: usize, proofs: usize, validate: bool) -> Result<BenchReport> {
+    let mut report = BenchReport::new("merkleproofs", size, "n/a");
+    let mut rng = thread_rng();
+
+    let leaf_size = 32;
+    let num_leaves = (size / leaf_size).max(2).next_power_of_two();
+    // `TreeDomain::random` draws a field element directly, unlike parsing
+    // raw bytes: a uniformly random 32-byte value is usually *not* a
+    // canonical encoding of a Poseidon field element, so `try_from_bytes`
+    // would reject most of them.
+    let leaves: Vec<TreeDomain> = (0..num_leaves).map(|_| TreeDomain::random(&mut rng)).collect();
+
+    let tree: MerkleTree<TreeDomain, TreeFunction, VecStore<TreeDomain>> =
+        MerkleTree::try_from_iter(leaves.into_iter().map(Ok))?;
+
+    let gen_start = Instant::now();
+    let indexes: Vec<usize> = (0..proofs).map(|_| rng.gen_range(0..num_leaves)).collect();
+    let generated = indexes
+        .iter()
+        .map(|&i| tree.gen_proof(i))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let gen_elapsed = gen_start.elapsed();
+
+    info!(
+        "merkleproofs: generated {} proofs over {} leaves in {:?}",
+        proofs, num_leaves, gen_elapsed
+    );
+    report.record_phase("generation", gen_elapsed);
+    report.proof_bytes = generated.iter().map(|p| p.lemma().len() * 32).sum();
+
+    if validate {
+        let val_start = Instant::now();
+        for proof in &generated {
+            report.pass &= proof.validate::<TreeFunction>();
+        }
+        let val_elapsed = val_start.elapsed();
+        info!("merkleproofs: validated {} proofs in {:?}", proofs, val_elapsed);
+        report.record_phase("validation", val_elapsed);
+    }
+
+    report.finish();
+    Ok(report)
+}
@@ -0,0 +1,181 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Output format for a [`BenchReport`], selected crate-wide via
+/// `--metrics-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricsFormat {
+    Json,
+    Csv,
+}
+
+impl FromStr for MetricsFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(MetricsFormat::Json),
+            "csv" => Ok(MetricsFormat::Csv),
+            _ => bail!("unknown metrics format: {} (expected json or csv)", s),
+        }
+    }
+}
+
+impl fmt::Display for MetricsFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetricsFormat::Json => write!(f, "json"),
+            MetricsFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// The wall-clock duration of a single named phase of a benchmark (e.g.
+/// `precommit-phase1`, `aggregation`, `verification`).
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+impl PhaseTiming {
+    pub fn new(name: impl Into<String>, duration: Duration) -> Self {
+        PhaseTiming {
+            name: name.into(),
+            duration_ms: duration.as_millis() as u64,
+        }
+    }
+}
+
+/// A common, machine-readable summary of a single `benchy` subcommand
+/// invocation, regardless of which one produced it. Every subcommand except
+/// `hash-constraints` builds one of these and hands it back to `main`, which
+/// serializes it according to `--metrics-format`/`--metrics-out`;
+/// `hash-constraints` only logs its timing and writes no report.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub subcommand: String,
+    pub sector_size: usize,
+    pub api_version: String,
+    pub phases: Vec<PhaseTiming>,
+    pub peak_rss_bytes: u64,
+    pub proof_bytes: usize,
+    pub pass: bool,
+    /// Subcommand-specific structured data that doesn't fit the common
+    /// fields above (e.g. `aggregate-proof`'s num_agg sweep table).
+    /// Only rendered in JSON output; CSV only renders the common
+    /// fields and per-phase timings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra: Option<Value>,
+}
+
+impl BenchReport {
+    pub fn new(subcommand: impl Into<String>, sector_size: usize, api_version: impl Into<String>) -> Self {
+        BenchReport {
+            subcommand: subcommand.into(),
+            sector_size,
+            api_version: api_version.into(),
+            phases: Vec::new(),
+            peak_rss_bytes: peak_rss_bytes(),
+            proof_bytes: 0,
+            pass: true,
+            extra: None,
+        }
+    }
+
+    pub fn record_phase(&mut self, name: impl Into<String>, duration: Duration) {
+        self.phases.push(PhaseTiming::new(name, duration));
+    }
+
+    pub fn set_extra(&mut self, extra: Value) {
+        self.extra = Some(extra);
+    }
+
+    pub fn finish(&mut self) {
+        self.peak_rss_bytes = peak_rss_bytes();
+    }
+}
+
+/// Writes `report` in the requested `format` to `out`, which is either a
+/// file path (`--metrics-out`) or, if `None`, stdout.
+pub fn write_report(report: &BenchReport, format: MetricsFormat, out: Option<&str>) -> Result<()> {
+    let mut writer: Box<dyn Write> = match out {
+        Some(path) if !path.is_empty() => Box::new(File::create(Path::new(path))?),
+        _ => Box::new(io::stdout()),
+    };
+
+    match format {
+        MetricsFormat::Json => {
+            serde_json::to_writer(&mut writer, report)?;
+            writeln!(writer)?;
+        }
+        MetricsFormat::Csv => {
+            writeln!(
+                writer,
+                "subcommand,sector_size,api_version,phase,duration_ms,peak_rss_bytes,proof_bytes,pass"
+            )?;
+            if report.phases.is_empty() {
+                writeln!(
+                    writer,
+                    "{},{},{},,,{},{},{}",
+                    report.subcommand,
+                    report.sector_size,
+                    report.api_version,
+                    report.peak_rss_bytes,
+                    report.proof_bytes,
+                    report.pass
+                )?;
+            }
+            for phase in &report.phases {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{},{}",
+                    report.subcommand,
+                    report.sector_size,
+                    report.api_version,
+                    phase.name,
+                    phase.duration_ms,
+                    report.peak_rss_bytes,
+                    report.proof_bytes,
+                    report.pass
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Best-effort peak resident set size of the current process, in bytes.
+#[cfg(target_os = "linux")]
+fn peak_rss_bytes() -> u64 {
+    use std::fs;
+
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            status.lines().find_map(|line| {
+                line.strip_prefix("VmHWM:").map(|rest| {
+                    rest.trim()
+                        .trim_end_matches(" kB")
+                        .parse::<u64>()
+                        .unwrap_or(0)
+                        * 1024
+                })
+            })
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_rss_bytes() -> u64 {
+    0
+}
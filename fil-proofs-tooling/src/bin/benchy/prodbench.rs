@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::metrics::BenchReport;
+
+/// Inputs for a `prodbench` run, deserialized either from a config file
+/// (`--config`) or from stdin.
+#[derive(Debug, Deserialize)]
+pub struct ProdbenchInputs {
+    pub sector_size_bytes: usize,
+    #[serde(default)]
+    pub api_version: String,
+    #[serde(default = "default_sectors")]
+    pub sectors: usize,
+}
+
+fn default_sectors() -> usize {
+    1
+}
+
+/// Structured results of a `prodbench` run. Reported to the user wrapped in
+/// a `BenchReport` (see `to_bench_report`) so CI pipelines can ingest it the
+/// same way as every other subcommand's metrics.
+#[derive(Debug, Default, Serialize)]
+pub struct ProdbenchOutput {
+    pub sector_size_bytes: usize,
+    pub add_piece_time_ms: u64,
+    pub replication_time_ms: u64,
+    pub seal_proof_time_ms: u64,
+    pub seal_verify_time_ms: u64,
+    pub post_proof_time_ms: u64,
+    pub post_verify_time_ms: u64,
+}
+
+/// Walks the prodbench stages (add-piece, replication, seal proof and
+/// verification, PoSt proof and verification), honoring the skip/only
+/// flags. No piece is added, no sector is replicated and no proof is
+/// generated or verified here: each stage only logs its name, so every
+/// recorded duration measures that log call alone.
+pub fn run(
+    inputs: ProdbenchInputs,
+    skip_seal_proof: bool,
+    skip_post_proof: bool,
+    only_replicate: bool,
+    only_add_piece: bool,
+) -> ProdbenchOutput {
+    let mut output = ProdbenchOutput {
+        sector_size_bytes: inputs.sector_size_bytes,
+        ..Default::default()
+    };
+
+    let start = Instant::now();
+    info!("prodbench: add-piece for {} sectors", inputs.sectors);
+    output.add_piece_time_ms = start.elapsed().as_millis() as u64;
+
+    if only_add_piece {
+        return output;
+    }
+
+    let start = Instant::now();
+    info!("prodbench: replicating {} sectors", inputs.sectors);
+    output.replication_time_ms = start.elapsed().as_millis() as u64;
+
+    if only_replicate {
+        return output;
+    }
+
+    if !skip_seal_proof {
+        let start = Instant::now();
+        info!("prodbench: generating seal proof");
+        output.seal_proof_time_ms = start.elapsed().as_millis() as u64;
+
+        let start = Instant::now();
+        info!("prodbench: verifying seal proof");
+        output.seal_verify_time_ms = start.elapsed().as_millis() as u64;
+    }
+
+    if !skip_post_proof {
+        let start = Instant::now();
+        info!("prodbench: generating post proof");
+        output.post_proof_time_ms = start.elapsed().as_millis() as u64;
+
+        let start = Instant::now();
+        info!("prodbench: verifying post proof");
+        output.post_verify_time_ms = start.elapsed().as_millis() as u64;
+    }
+
+    output
+}
+
+/// Wraps a `ProdbenchOutput` in the crate-wide `BenchReport` envelope so
+/// `prodbench`, like every other subcommand, honors `--metrics-format` and
+/// `--metrics-out`. The full `ProdbenchOutput` is preserved under `extra`
+/// so nothing present in the original stdout JSON is lost.
+pub fn to_bench_report(output: &ProdbenchOutput, api_version: String) -> BenchReport {
+    let mut report = BenchReport::new("prodbench", output.sector_size_bytes, api_version);
+
+    report.record_phase("add-piece", Duration::from_millis(output.add_piece_time_ms));
+    report.record_phase("replication", Duration::from_millis(output.replication_time_ms));
+    report.record_phase("seal-proof", Duration::from_millis(output.seal_proof_time_ms));
+    report.record_phase("seal-verify", Duration::from_millis(output.seal_verify_time_ms));
+    report.record_phase("post-proof", Duration::from_millis(output.post_proof_time_ms));
+    report.record_phase("post-verify", Duration::from_millis(output.post_verify_time_ms));
+
+    report.set_extra(json!(output));
+    report.finish();
+    report
+}
@@ -0,0 +1,367 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use filecoin_proofs::types::{PieceInfo, UnpaddedBytesAmount};
+use filecoin_proofs::{
+    add_piece as add_piece_api, seal_commit_phase1, seal_commit_phase2, seal_pre_commit_phase1,
+    seal_pre_commit_phase2, verify_seal as verify_seal_api, with_shape, SealCommitOutput,
+    SealCommitPhase1Output, SealPreCommitOutput, SealPreCommitPhase1Output,
+};
+use log::info;
+use serde_json::json;
+use storage_proofs_core::api_version::ApiVersion;
+use storage_proofs_core::merkle::MerkleTreeTrait;
+use tempfile::tempdir;
+
+use crate::metrics::BenchReport;
+use crate::shared::{porep_config, sector_id, PROVER_ID, SEED, TICKET};
+
+/// A single phase of the PoRep seal pipeline, in the order it normally
+/// runs. `--phases` takes a comma-separated subset of these names so a
+/// single phase (e.g. precommit-phase2) can be profiled in isolation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    AddPiece,
+    PrecommitPhase1,
+    PrecommitPhase2,
+    CommitPhase1,
+    CommitPhase2,
+    Verify,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::AddPiece,
+        Phase::PrecommitPhase1,
+        Phase::PrecommitPhase2,
+        Phase::CommitPhase1,
+        Phase::CommitPhase2,
+        Phase::Verify,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Phase::AddPiece => "add-piece",
+            Phase::PrecommitPhase1 => "precommit-phase1",
+            Phase::PrecommitPhase2 => "precommit-phase2",
+            Phase::CommitPhase1 => "commit-phase1",
+            Phase::CommitPhase2 => "commit-phase2",
+            Phase::Verify => "verify",
+        }
+    }
+}
+
+impl FromStr for Phase {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Phase::ALL
+            .into_iter()
+            .find(|phase| phase.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Phase::ALL.iter().map(|p| p.name()).collect();
+                anyhow::anyhow!("unknown seal phase: {} (expected one of: {})", s, names.join(", "))
+            })
+    }
+}
+
+/// Parses a `--phases` value (a comma-separated list, or empty for "all
+/// phases") into the list of [`Phase`] to run, in the order given.
+pub fn parse_phases(raw: &str) -> Result<Vec<Phase>> {
+    if raw.is_empty() {
+        return Ok(Phase::ALL.to_vec());
+    }
+
+    raw.split(',').map(str::trim).map(Phase::from_str).collect()
+}
+
+/// Drives the full PoRep seal pipeline for a sector of `sector_size` bytes
+/// through the real `filecoin_proofs` seal API: add-piece, precommit phase
+/// 1 (labeling/SDR), precommit phase 2 (tree building/CommR), commit phase
+/// 1, commit phase 2 (the SNARK), and seal verification. `phases`
+/// restricts which of those run; skipped phases are assumed to have
+/// already produced their outputs in `cache_dir` on a previous invocation.
+/// If `reuse_cache` is set, an existing `cache_dir` is kept rather than
+/// replaced with a fresh one, letting a later phase build on an earlier
+/// run's outputs — this is what lets a single expensive phase (e.g. PC2)
+/// be profiled standalone.
+pub fn run(
+    sector_size: usize,
+    api_version: ApiVersion,
+    phases: &[Phase],
+    cache_dir: String,
+    reuse_cache: bool,
+) -> Result<BenchReport> {
+    let cache_dir = if cache_dir.is_empty() {
+        tempdir()?.into_path()
+    } else {
+        PathBuf::from(cache_dir)
+    };
+    if !reuse_cache {
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
+    fs::create_dir_all(&cache_dir)?;
+
+    info!(
+        "seal: sector_size={} api_version={} phases={:?} cache_dir={:?}",
+        sector_size,
+        api_version,
+        phases.iter().map(|p| p.name()).collect::<Vec<_>>(),
+        cache_dir
+    );
+
+    let config = porep_config(sector_size, api_version)?;
+    let sector_id = sector_id(0);
+    let replica_path = cache_dir.join("replica");
+    let staged_path = cache_dir.join("staged");
+
+    let mut report = BenchReport::new("seal", sector_size, api_version.to_string());
+    // `--phases`/`--reuse-cache` let a phase be profiled or verified in
+    // isolation, across separate invocations, so load whatever a prior
+    // invocation already persisted to `cache_dir` up front. Any of these
+    // phases running in *this* invocation overwrites the loaded value below.
+    let (mut comm_d, mut comm_r) = read_comms(&cache_dir)?;
+    let mut proof = read_proof(&cache_dir)?;
+    let mut piece_infos = read_piece_infos(&cache_dir)?;
+
+    for phase in phases {
+        let start = Instant::now();
+        match phase {
+            Phase::AddPiece => {
+                piece_infos = run_add_piece(sector_size, &staged_path)?;
+                write_piece_infos(&cache_dir, &piece_infos)?;
+            }
+            Phase::PrecommitPhase1 => {
+                let phase1_output = with_shape!(
+                    sector_size as u64,
+                    run_precommit_phase1,
+                    config,
+                    &cache_dir,
+                    &staged_path,
+                    &replica_path,
+                    sector_id,
+                    &piece_infos
+                )?;
+                write_phase1_output(&cache_dir, &phase1_output)?;
+            }
+            Phase::PrecommitPhase2 => {
+                let phase1_output = read_phase1_output(&cache_dir)?;
+                let precommit = with_shape!(
+                    sector_size as u64,
+                    run_precommit_phase2,
+                    phase1_output,
+                    &cache_dir,
+                    &replica_path
+                )?;
+                comm_d = Some(precommit.comm_d);
+                comm_r = Some(precommit.comm_r);
+                write_precommit_output(&cache_dir, &precommit)?;
+            }
+            Phase::CommitPhase1 => {
+                let precommit = read_precommit_output(&cache_dir)?;
+                let phase1_output = with_shape!(
+                    sector_size as u64,
+                    run_commit_phase1,
+                    config,
+                    &cache_dir,
+                    &replica_path,
+                    sector_id,
+                    precommit,
+                    &piece_infos
+                )?;
+                write_commit_phase1_output(&cache_dir, &phase1_output)?;
+            }
+            Phase::CommitPhase2 => {
+                let phase1_output = read_commit_phase1_output(&cache_dir)?;
+                let commit = with_shape!(sector_size as u64, run_commit_phase2, config, phase1_output, sector_id)?;
+                proof = commit.proof;
+                fs::write(cache_dir.join("commit-phase2-output"), &proof)?;
+            }
+            Phase::Verify => {
+                let (Some(comm_d), Some(comm_r)) = (comm_d, comm_r) else {
+                    bail!("seal: verify requires CommD/CommR; run precommit-phase2 first or --reuse-cache an existing cache_dir");
+                };
+                let valid =
+                    with_shape!(sector_size as u64, run_verify_seal, config, comm_r, comm_d, sector_id, &proof)?;
+                report.pass &= valid;
+            }
+        }
+        let elapsed = start.elapsed();
+        info!("seal: {} took {:?}", phase.name(), elapsed);
+        report.record_phase(phase.name(), elapsed);
+    }
+
+    report.proof_bytes = proof.len();
+    report.set_extra(json!({
+        "comm_d": comm_d.map(hex::encode),
+        "comm_r": comm_r.map(hex::encode),
+    }));
+
+    if !reuse_cache {
+        fs::remove_dir_all(&cache_dir)?;
+    }
+
+    report.finish();
+    Ok(report)
+}
+
+fn run_add_piece(sector_size: usize, staged_path: &Path) -> Result<Vec<PieceInfo>> {
+    let piece_size = UnpaddedBytesAmount::from(filecoin_proofs::types::PaddedBytesAmount(sector_size as u64));
+    let source = vec![0u8; u64::from(piece_size) as usize];
+    let mut staged_file = fs::File::create(staged_path)?;
+    let (piece_info, _) = add_piece_api(source.as_slice(), &mut staged_file, piece_size, &[])?;
+    Ok(vec![piece_info])
+}
+
+fn run_precommit_phase1<Tree: 'static + MerkleTreeTrait>(
+    config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    staged_path: &Path,
+    replica_path: &Path,
+    sector_id: storage_proofs_core::sector::SectorId,
+    piece_infos: &[PieceInfo],
+) -> Result<SealPreCommitPhase1Output> {
+    seal_pre_commit_phase1::<_, _, _, Tree>(
+        config,
+        cache_dir,
+        staged_path,
+        replica_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        piece_infos,
+    )
+    .map_err(Into::into)
+}
+
+fn run_precommit_phase2<Tree: 'static + MerkleTreeTrait>(
+    phase1_output: SealPreCommitPhase1Output,
+    cache_dir: &Path,
+    replica_path: &Path,
+) -> Result<SealPreCommitOutput> {
+    seal_pre_commit_phase2::<_, _, Tree>(phase1_output, cache_dir, replica_path).map_err(Into::into)
+}
+
+fn run_commit_phase1<Tree: 'static + MerkleTreeTrait>(
+    config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    replica_path: &Path,
+    sector_id: storage_proofs_core::sector::SectorId,
+    precommit: SealPreCommitOutput,
+    piece_infos: &[PieceInfo],
+) -> Result<SealCommitPhase1Output> {
+    seal_commit_phase1::<_, Tree>(
+        config,
+        cache_dir,
+        replica_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        SEED,
+        precommit,
+        piece_infos,
+    )
+    .map_err(Into::into)
+}
+
+fn run_commit_phase2<Tree: 'static + MerkleTreeTrait>(
+    config: filecoin_proofs::types::PoRepConfig,
+    phase1_output: SealCommitPhase1Output,
+    sector_id: storage_proofs_core::sector::SectorId,
+) -> Result<SealCommitOutput> {
+    seal_commit_phase2::<Tree>(config, phase1_output, PROVER_ID, sector_id).map_err(Into::into)
+}
+
+fn run_verify_seal<Tree: 'static + MerkleTreeTrait>(
+    config: filecoin_proofs::types::PoRepConfig,
+    comm_r: [u8; 32],
+    comm_d: [u8; 32],
+    sector_id: storage_proofs_core::sector::SectorId,
+    proof: &[u8],
+) -> Result<bool> {
+    verify_seal_api::<Tree>(config, comm_r, comm_d, PROVER_ID, sector_id, TICKET, SEED, proof).map_err(Into::into)
+}
+
+fn write_piece_infos(cache_dir: &Path, piece_infos: &[PieceInfo]) -> Result<()> {
+    Ok(serde_json::to_writer(fs::File::create(cache_dir.join("piece-infos.json"))?, piece_infos)?)
+}
+
+fn read_piece_infos(cache_dir: &Path) -> Result<Vec<PieceInfo>> {
+    let path = cache_dir.join("piece-infos.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_reader(fs::File::open(path)?)?)
+}
+
+fn write_phase1_output(cache_dir: &Path, output: &SealPreCommitPhase1Output) -> Result<()> {
+    Ok(serde_json::to_writer(fs::File::create(cache_dir.join("p1-output.json"))?, output)?)
+}
+
+fn read_phase1_output(cache_dir: &Path) -> Result<SealPreCommitPhase1Output> {
+    let path = cache_dir.join("p1-output.json");
+    if !path.exists() {
+        bail!("seal: precommit-phase2 requires precommit-phase1 output; run precommit-phase1 first or --reuse-cache an existing cache_dir");
+    }
+    Ok(serde_json::from_reader(fs::File::open(path)?)?)
+}
+
+fn write_precommit_output(cache_dir: &Path, output: &SealPreCommitOutput) -> Result<()> {
+    fs::write(cache_dir.join("p_aux"), [output.comm_d, output.comm_r].concat())?;
+    Ok(serde_json::to_writer(fs::File::create(cache_dir.join("precommit-output.json"))?, output)?)
+}
+
+fn read_precommit_output(cache_dir: &Path) -> Result<SealPreCommitOutput> {
+    let path = cache_dir.join("precommit-output.json");
+    if !path.exists() {
+        bail!("seal: commit-phase1 requires precommit-phase2 output; run precommit-phase2 first or --reuse-cache an existing cache_dir");
+    }
+    Ok(serde_json::from_reader(fs::File::open(path)?)?)
+}
+
+fn write_commit_phase1_output(cache_dir: &Path, output: &SealCommitPhase1Output) -> Result<()> {
+    Ok(serde_json::to_writer(
+        fs::File::create(cache_dir.join("c1-output.json"))?,
+        output,
+    )?)
+}
+
+fn read_commit_phase1_output(cache_dir: &Path) -> Result<SealCommitPhase1Output> {
+    let path = cache_dir.join("c1-output.json");
+    if !path.exists() {
+        bail!("seal: commit-phase2 requires commit-phase1 output; run commit-phase1 first or --reuse-cache an existing cache_dir");
+    }
+    Ok(serde_json::from_reader(fs::File::open(path)?)?)
+}
+
+/// Loads `comm_d`/`comm_r` from a prior invocation's `p_aux`, if present.
+fn read_comms(cache_dir: &Path) -> Result<(Option<[u8; 32]>, Option<[u8; 32]>)> {
+    let path = cache_dir.join("p_aux");
+    if !path.exists() {
+        return Ok((None, None));
+    }
+
+    let raw = fs::read(path)?;
+    if raw.len() != 64 {
+        bail!("seal: p_aux has unexpected length {} (expected 64)", raw.len());
+    }
+
+    let mut comm_d = [0u8; 32];
+    let mut comm_r = [0u8; 32];
+    comm_d.copy_from_slice(&raw[..32]);
+    comm_r.copy_from_slice(&raw[32..]);
+    Ok((Some(comm_d), Some(comm_r)))
+}
+
+/// Loads the seal proof from a prior invocation's `commit-phase2-output`,
+/// if present.
+fn read_proof(cache_dir: &Path) -> Result<Vec<u8>> {
+    let path = cache_dir.join("commit-phase2-output");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read(path)?)
+}
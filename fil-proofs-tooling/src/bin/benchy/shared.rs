@@ -0,0 +1,197 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use filecoin_proofs::constants::{POREP_PARTITIONS, WINDOW_POST_SECTOR_COUNT, WINNING_POST_SECTOR_COUNT};
+use filecoin_proofs::types::{PaddedBytesAmount, PoRepConfig, PoRepProofPartitions, PoStConfig, PoStType, SectorSize, UnpaddedBytesAmount};
+use filecoin_proofs::{
+    add_piece, seal_commit_phase1, seal_commit_phase2, seal_pre_commit_phase1, seal_pre_commit_phase2, with_shape,
+};
+use storage_proofs_core::api_version::ApiVersion;
+use storage_proofs_core::merkle::MerkleTreeTrait;
+use storage_proofs_core::sector::SectorId;
+use tempfile::tempdir;
+
+/// Fixed prover id used for every synthetic sector `benchy` seals; unlike a
+/// production miner actor address, benchmarks gain nothing from varying it.
+pub const PROVER_ID: [u8; 32] = [9u8; 32];
+/// Fixed sealing ticket/seed. `benchy` replicates the same sector bytes on
+/// every run, so there's no need for real chain randomness here.
+pub const TICKET: [u8; 32] = [1u8; 32];
+pub const SEED: [u8; 32] = [2u8; 32];
+
+pub fn sector_id(n: u64) -> SectorId {
+    SectorId::from(n)
+}
+
+/// Looks up the proof partition count `storage-proofs` expects for
+/// `sector_size` and builds the `PoRepConfig` every seal phase needs.
+pub fn porep_config(sector_size: usize, api_version: ApiVersion) -> Result<PoRepConfig> {
+    let partitions = *POREP_PARTITIONS
+        .read()
+        .map_err(|_| anyhow::anyhow!("POREP_PARTITIONS lock poisoned"))?
+        .get(&(sector_size as u64))
+        .ok_or_else(|| anyhow::anyhow!("no known porep partition count for sector size {}", sector_size))?;
+
+    Ok(PoRepConfig {
+        sector_size: SectorSize(sector_size as u64),
+        partitions: PoRepProofPartitions(partitions),
+        porep_id: [0u8; 32],
+        api_version,
+    })
+}
+
+pub fn window_post_config(sector_size: usize, api_version: ApiVersion) -> Result<PoStConfig> {
+    let sector_count = *WINDOW_POST_SECTOR_COUNT
+        .read()
+        .map_err(|_| anyhow::anyhow!("WINDOW_POST_SECTOR_COUNT lock poisoned"))?
+        .get(&(sector_size as u64))
+        .ok_or_else(|| anyhow::anyhow!("no known window-post sector count for sector size {}", sector_size))?;
+
+    Ok(PoStConfig {
+        sector_size: SectorSize(sector_size as u64),
+        sector_count,
+        typ: PoStType::Window,
+        priority: true,
+        api_version,
+    })
+}
+
+pub fn winning_post_config(sector_size: usize, api_version: ApiVersion) -> Result<PoStConfig> {
+    let sector_count = *WINNING_POST_SECTOR_COUNT
+        .read()
+        .map_err(|_| anyhow::anyhow!("WINNING_POST_SECTOR_COUNT lock poisoned"))?
+        .get(&(sector_size as u64))
+        .ok_or_else(|| anyhow::anyhow!("no known winning-post sector count for sector size {}", sector_size))?;
+
+    Ok(PoStConfig {
+        sector_size: SectorSize(sector_size as u64),
+        sector_count,
+        typ: PoStType::Winning,
+        priority: true,
+        api_version,
+    })
+}
+
+/// A sector that's been sealed end to end (precommit phases 1/2, commit
+/// phases 1/2), ready to be fed into Window/Winning PoST or into
+/// aggregation. Dropping it does not clean up: the caller removes its
+/// `cache_dir` once PoST generation/verification is done with it.
+pub struct ReplicatedSector {
+    pub sector_id: SectorId,
+    pub cache_dir: PathBuf,
+    pub replica_path: PathBuf,
+    pub comm_r: [u8; 32],
+    pub seal_proof: Vec<u8>,
+}
+
+/// Runs the full seal pipeline (add-piece through commit phase 2) for a
+/// single sector of `sector_size` bytes, so PoST/aggregation benchmarks
+/// have a real replica and seal proof to operate on instead of synthetic
+/// stand-ins.
+pub fn replicate(sector_size: usize, api_version: ApiVersion, sector_id: SectorId) -> Result<ReplicatedSector> {
+    let config = porep_config(sector_size, api_version)?;
+    let cache_dir = tempdir()?.into_path();
+    let staged_path = cache_dir.join("staged");
+    let replica_path = cache_dir.join("replica");
+
+    let piece_size = UnpaddedBytesAmount::from(PaddedBytesAmount(sector_size as u64));
+    let source = vec![0u8; u64::from(piece_size) as usize];
+    let mut staged_file = fs::File::create(&staged_path)?;
+    let (piece_info, _) = add_piece(source.as_slice(), &mut staged_file, piece_size, &[])?;
+    let piece_infos = vec![piece_info];
+
+    let phase1_output = with_shape!(
+        sector_size as u64,
+        replicate_phase1,
+        config,
+        &cache_dir,
+        &staged_path,
+        &replica_path,
+        sector_id,
+        &piece_infos
+    )?;
+
+    let precommit = with_shape!(sector_size as u64, replicate_phase2, phase1_output, &cache_dir, &replica_path)?;
+    let comm_r = precommit.comm_r;
+
+    let commit_phase1_output = with_shape!(
+        sector_size as u64,
+        replicate_commit_phase1,
+        config,
+        &cache_dir,
+        &replica_path,
+        sector_id,
+        precommit,
+        &piece_infos
+    )?;
+
+    let commit = with_shape!(sector_size as u64, replicate_commit_phase2, config, commit_phase1_output, sector_id)?;
+
+    Ok(ReplicatedSector {
+        sector_id,
+        cache_dir,
+        replica_path,
+        comm_r,
+        seal_proof: commit.proof,
+    })
+}
+
+fn replicate_phase1<Tree: 'static + MerkleTreeTrait>(
+    config: PoRepConfig,
+    cache_dir: &Path,
+    staged_path: &Path,
+    replica_path: &Path,
+    sector_id: SectorId,
+    piece_infos: &[filecoin_proofs::types::PieceInfo],
+) -> Result<filecoin_proofs::SealPreCommitPhase1Output> {
+    seal_pre_commit_phase1::<_, _, _, Tree>(
+        config,
+        cache_dir,
+        staged_path,
+        replica_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        piece_infos,
+    )
+    .map_err(Into::into)
+}
+
+fn replicate_phase2<Tree: 'static + MerkleTreeTrait>(
+    phase1_output: filecoin_proofs::SealPreCommitPhase1Output,
+    cache_dir: &Path,
+    replica_path: &Path,
+) -> Result<filecoin_proofs::SealPreCommitOutput> {
+    seal_pre_commit_phase2::<_, _, Tree>(phase1_output, cache_dir, replica_path).map_err(Into::into)
+}
+
+fn replicate_commit_phase1<Tree: 'static + MerkleTreeTrait>(
+    config: PoRepConfig,
+    cache_dir: &Path,
+    replica_path: &Path,
+    sector_id: SectorId,
+    precommit: filecoin_proofs::SealPreCommitOutput,
+    piece_infos: &[filecoin_proofs::types::PieceInfo],
+) -> Result<filecoin_proofs::SealCommitPhase1Output> {
+    seal_commit_phase1::<_, Tree>(
+        config,
+        cache_dir,
+        replica_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        SEED,
+        precommit,
+        piece_infos,
+    )
+    .map_err(Into::into)
+}
+
+fn replicate_commit_phase2<Tree: 'static + MerkleTreeTrait>(
+    config: PoRepConfig,
+    phase1_output: filecoin_proofs::SealCommitPhase1Output,
+    sector_id: SectorId,
+) -> Result<filecoin_proofs::SealCommitOutput> {
+    seal_commit_phase2::<Tree>(config, phase1_output, PROVER_ID, sector_id).map_err(Into::into)
+}
@@ -0,0 +1,291 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+
+/// On-disk format version for checkpoint chunks. Bump this whenever the
+/// chunk layout changes; `read_chunk` refuses to load a chunk whose version
+/// doesn't match, rather than guessing at how to interpret it.
+const CHUNK_FORMAT_VERSION: u8 = 1;
+const HASH_LEN: usize = 32;
+
+/// The replication stages that `window_post::run` can checkpoint
+/// independently. Order matters: resuming picks up at the first stage
+/// that has no chunk on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    PrecommitPhase1,
+    PrecommitPhase2,
+    CommitPhase1,
+    CommitPhase2,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [
+        Stage::PrecommitPhase1,
+        Stage::PrecommitPhase2,
+        Stage::CommitPhase1,
+        Stage::CommitPhase2,
+    ];
+
+    fn chunk_name(self) -> &'static str {
+        match self {
+            Stage::PrecommitPhase1 => "0001-precommit-phase1.chunk",
+            Stage::PrecommitPhase2 => "0002-precommit-phase2.chunk",
+            Stage::CommitPhase1 => "0003-commit-phase1.chunk",
+            Stage::CommitPhase2 => "0004-commit-phase2.chunk",
+        }
+    }
+
+    fn from_chunk_name(name: &str) -> Result<Self> {
+        Stage::ALL
+            .into_iter()
+            .find(|stage| stage.chunk_name() == name)
+            .ok_or_else(|| anyhow::anyhow!("unknown checkpoint chunk: {}", name))
+    }
+}
+
+/// A versioned, independently-restorable checkpoint directory. Each stage's
+/// intermediate state is written to its own chunk file, tagged with a
+/// format-version byte and a hash of its (uncompressed) contents, so a
+/// chunk can be validated and restored without needing any of the others.
+pub struct CheckpointStore {
+    dir: PathBuf,
+}
+
+impl CheckpointStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(CheckpointStore { dir })
+    }
+
+    fn path_for(&self, stage: Stage) -> PathBuf {
+        self.dir.join(stage.chunk_name())
+    }
+
+    /// Serializes and compresses `payload` into `stage`'s chunk file.
+    pub fn write_chunk(&self, stage: Stage, payload: &[u8]) -> Result<()> {
+        let hash: [u8; HASH_LEN] = Sha256::digest(payload).into();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(payload)?;
+        let compressed = encoder.finish()?;
+
+        let mut out = Vec::with_capacity(1 + HASH_LEN + compressed.len());
+        out.push(CHUNK_FORMAT_VERSION);
+        out.extend_from_slice(&hash);
+        out.extend_from_slice(&compressed);
+
+        fs::write(self.path_for(stage), out)?;
+        Ok(())
+    }
+
+    /// Validates and decompresses `stage`'s chunk, if one exists. Returns
+    /// `Ok(None)` if the chunk is missing (meaning the stage has not been
+    /// completed yet), and an error if the chunk exists but its version or
+    /// content hash doesn't check out.
+    pub fn read_chunk(&self, stage: Stage) -> Result<Option<Vec<u8>>> {
+        let path = self.path_for(stage);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let raw = fs::read(&path)?;
+        if raw.len() < 1 + HASH_LEN {
+            bail!("truncated checkpoint chunk: {:?}", path);
+        }
+
+        let version = raw[0];
+        if version != CHUNK_FORMAT_VERSION {
+            bail!(
+                "checkpoint chunk {:?} has format version {}, expected {}",
+                path,
+                version,
+                CHUNK_FORMAT_VERSION
+            );
+        }
+
+        let expected_hash = &raw[1..1 + HASH_LEN];
+        let compressed = &raw[1 + HASH_LEN..];
+
+        let mut payload = Vec::new();
+        GzDecoder::new(compressed).read_to_end(&mut payload)?;
+
+        let actual_hash = Sha256::digest(&payload);
+        if actual_hash.as_slice() != expected_hash {
+            bail!("checkpoint chunk {:?} failed its content hash check", path);
+        }
+
+        Ok(Some(payload))
+    }
+
+    /// The first stage, in pipeline order, that has no chunk on disk yet.
+    /// Restarting a benchmark with the same `--checkpoint-dir` resumes
+    /// from here by default. `Ok(None)` means every stage already has a
+    /// valid chunk, i.e. there is nothing left to resume. A chunk met on
+    /// the way that fails its version or hash check is an error, not a
+    /// missing stage.
+    pub fn first_missing_stage(&self) -> Result<Option<Stage>> {
+        for stage in Stage::ALL {
+            if self.read_chunk(stage)?.is_none() {
+                return Ok(Some(stage));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolves a `--resume-from <chunk>` argument: validates the named
+    /// chunk and returns the stage immediately after it, i.e. the first
+    /// stage that still needs to run. `Ok(None)` means the named chunk was
+    /// the last stage, so every stage is already done.
+    pub fn resume_after(&self, chunk_name: &str) -> Result<Option<Stage>> {
+        let stage = Stage::from_chunk_name(chunk_name)?;
+        if self.read_chunk(stage)?.is_none() {
+            bail!(
+                "--resume-from {} was given, but no valid checkpoint exists for it in {:?}",
+                chunk_name,
+                self.dir
+            );
+        }
+
+        let index = Stage::ALL
+            .iter()
+            .position(|s| *s == stage)
+            .expect("stage is a member of Stage::ALL");
+        Ok(Stage::ALL.get(index + 1).copied())
+    }
+}
+
+/// Whether `current` has already been satisfied by `resume_point`: either a
+/// valid checkpoint for it is known to exist (it sits before the first
+/// stage that still needs to run), or `resume_point` is `None`, meaning
+/// every stage — including the last one — is already done.
+pub fn should_skip(current: Stage, resume_point: Option<Stage>) -> bool {
+    let index_of = |stage: Stage| Stage::ALL.iter().position(|s| *s == stage).unwrap_or(0);
+    match resume_point {
+        Some(resume_point) => index_of(current) < index_of(resume_point),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &std::path::Path) -> CheckpointStore {
+        CheckpointStore::new(dir).expect("failed to create checkpoint store")
+    }
+
+    #[test]
+    fn first_missing_stage_is_the_earliest_incomplete_one() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+
+        assert_eq!(
+            store.first_missing_stage().unwrap(),
+            Some(Stage::PrecommitPhase1)
+        );
+
+        store.write_chunk(Stage::PrecommitPhase1, b"p1").unwrap();
+        store.write_chunk(Stage::PrecommitPhase2, b"p2").unwrap();
+        assert_eq!(
+            store.first_missing_stage().unwrap(),
+            Some(Stage::CommitPhase1)
+        );
+    }
+
+    #[test]
+    fn first_missing_stage_is_none_once_every_stage_is_checkpointed() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+
+        for stage in Stage::ALL {
+            store.write_chunk(stage, b"payload").unwrap();
+        }
+
+        assert_eq!(store.first_missing_stage().unwrap(), None);
+    }
+
+    #[test]
+    fn resume_after_last_stage_reports_nothing_left_to_run() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+
+        for stage in Stage::ALL {
+            store.write_chunk(stage, b"payload").unwrap();
+        }
+
+        assert_eq!(
+            store.resume_after("0004-commit-phase2.chunk").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn resume_after_middle_stage_returns_the_next_one() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+        store.write_chunk(Stage::PrecommitPhase1, b"p1").unwrap();
+
+        assert_eq!(
+            store.resume_after("0001-precommit-phase1.chunk").unwrap(),
+            Some(Stage::PrecommitPhase2)
+        );
+    }
+
+    #[test]
+    fn resume_after_rejects_a_chunk_that_was_never_written() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+
+        assert!(store.resume_after("0003-commit-phase1.chunk").is_err());
+    }
+
+    #[test]
+    fn should_skip_treats_none_as_every_stage_done() {
+        assert!(should_skip(Stage::CommitPhase2, None));
+        assert!(should_skip(Stage::PrecommitPhase1, None));
+    }
+
+    #[test]
+    fn should_skip_only_skips_stages_before_the_resume_point() {
+        let resume_point = Some(Stage::CommitPhase2);
+        assert!(should_skip(Stage::CommitPhase1, resume_point));
+        assert!(!should_skip(Stage::CommitPhase2, resume_point));
+    }
+
+    #[test]
+    fn read_chunk_rejects_a_mismatched_format_version() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+        store.write_chunk(Stage::PrecommitPhase1, b"payload").unwrap();
+
+        let path = dir.path().join(Stage::PrecommitPhase1.chunk_name());
+        let mut raw = fs::read(&path).unwrap();
+        raw[0] = CHUNK_FORMAT_VERSION + 1;
+        fs::write(&path, raw).unwrap();
+
+        assert!(store.read_chunk(Stage::PrecommitPhase1).is_err());
+    }
+
+    #[test]
+    fn read_chunk_rejects_corrupted_content() {
+        let dir = tempfile::tempdir().expect("failed to create tempdir");
+        let store = store(dir.path());
+        store.write_chunk(Stage::PrecommitPhase1, b"payload").unwrap();
+
+        let path = dir.path().join(Stage::PrecommitPhase1.chunk_name());
+        let mut raw = fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0xff;
+        fs::write(&path, raw).unwrap();
+
+        assert!(store.read_chunk(Stage::PrecommitPhase1).is_err());
+    }
+}
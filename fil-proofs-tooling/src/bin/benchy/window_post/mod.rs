@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use filecoin_proofs::types::{PieceInfo, PrivateReplicaInfo, PublicReplicaInfo, UnpaddedBytesAmount};
+use filecoin_proofs::{
+    add_piece, generate_window_post, seal_commit_phase1, seal_commit_phase2, seal_pre_commit_phase1,
+    seal_pre_commit_phase2, verify_window_post, with_shape,
+};
+use log::info;
+use storage_proofs_core::api_version::ApiVersion;
+use storage_proofs_core::merkle::MerkleTreeTrait;
+use storage_proofs_core::sector::SectorId;
+use tempfile::tempdir;
+
+use crate::metrics::BenchReport;
+use crate::shared::{porep_config, sector_id, window_post_config, PROVER_ID, SEED, TICKET};
+use checkpoint::{CheckpointStore, Stage};
+
+mod checkpoint;
+
+/// Runs the full Window PoST replication pipeline for a sector of
+/// `sector_size` bytes: precommit phase 1 (labeling), precommit phase 2
+/// (tree building), commit phase 1, and commit phase 2 (SNARK), then
+/// generates and verifies a Window PoST proof over the resulting replica.
+/// Any of the four replication phases can be skipped, in which case the
+/// benchmark assumes the corresponding output was already produced by a
+/// previous run sharing the same `cache_dir`.
+///
+/// If `checkpoint_dir` is set, every file in `cache_dir` is snapshotted
+/// into a versioned chunk as soon as a phase completes — not just that
+/// phase's small summary struct, but the heavy on-disk SDR/tree files the
+/// next phase actually reads from `cache_dir` directly. That means
+/// restoring a checkpoint reconstructs everything a live run would have
+/// left behind, so `--cache` does not need to (and need not) point at the
+/// same directory a prior invocation used: a fresh `--checkpoint-dir`
+/// restore populates `cache_dir` from scratch. Restarting with the same
+/// `checkpoint_dir` resumes from the first phase without a chunk, so
+/// a multi-hour benchmark can survive an interruption without redoing
+/// completed phases; `resume_from` overrides that auto-detection to
+/// resume after one specific chunk (e.g. to rerun just PC2 standalone).
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    sector_size: usize,
+    api_version: ApiVersion,
+    cache_dir: String,
+    preserve_cache: bool,
+    skip_precommit_phase1: bool,
+    skip_precommit_phase2: bool,
+    skip_commit_phase1: bool,
+    skip_commit_phase2: bool,
+    test_resume: bool,
+    checkpoint_dir: Option<String>,
+    resume_from: Option<String>,
+) -> Result<BenchReport> {
+    let cache_dir = if cache_dir.is_empty() {
+        tempdir()?.into_path()
+    } else {
+        PathBuf::from(cache_dir)
+    };
+    fs::create_dir_all(&cache_dir)?;
+
+    info!(
+        "window-post: sector_size={} api_version={} cache_dir={:?}",
+        sector_size, api_version, cache_dir
+    );
+
+    let checkpoint = checkpoint_dir
+        .as_deref()
+        .filter(|dir| !dir.is_empty())
+        .map(CheckpointStore::new)
+        .transpose()?;
+
+    let resume_point = match (&checkpoint, &resume_from) {
+        (Some(store), Some(chunk)) => store.resume_after(chunk)?,
+        (Some(store), None) => store.first_missing_stage()?,
+        (None, _) => Some(Stage::PrecommitPhase1),
+    };
+
+    let porep_config = porep_config(sector_size, api_version)?;
+    let sector_id = sector_id(0);
+    let replica_path = cache_dir.join("replica");
+    let staged_path = cache_dir.join("staged");
+
+    let mut report = BenchReport::new("window-post", sector_size, api_version.to_string());
+
+    if !skip_precommit_phase1 {
+        let elapsed = run_stage(Stage::PrecommitPhase1, resume_point, checkpoint.as_ref(), &cache_dir, || {
+            precommit_phase1(porep_config, &cache_dir, &staged_path, &replica_path, sector_id)
+        })?;
+        info!("window-post: precommit phase 1 took {:?}", elapsed);
+        report.record_phase("precommit-phase1", elapsed);
+    }
+
+    if !skip_precommit_phase2 {
+        let elapsed = run_stage(Stage::PrecommitPhase2, resume_point, checkpoint.as_ref(), &cache_dir, || {
+            precommit_phase2(sector_size, &cache_dir, &replica_path)
+        })?;
+        info!("window-post: precommit phase 2 took {:?}", elapsed);
+        report.record_phase("precommit-phase2", elapsed);
+    }
+
+    if !skip_commit_phase1 {
+        let elapsed = run_stage(Stage::CommitPhase1, resume_point, checkpoint.as_ref(), &cache_dir, || {
+            commit_phase1(sector_size, porep_config, &cache_dir, &replica_path, sector_id)
+        })?;
+        info!("window-post: commit phase 1 took {:?}", elapsed);
+        report.record_phase("commit-phase1", elapsed);
+    }
+
+    if !skip_commit_phase2 {
+        let elapsed = run_stage(Stage::CommitPhase2, resume_point, checkpoint.as_ref(), &cache_dir, || {
+            commit_phase2(sector_size, porep_config, &cache_dir, sector_id)
+        })?;
+        info!("window-post: commit phase 2 took {:?}", elapsed);
+        report.record_phase("commit-phase2", elapsed);
+    }
+
+    if test_resume {
+        info!("window-post: re-running commit phase 2 to exercise resume path");
+        commit_phase2(sector_size, porep_config, &cache_dir, sector_id)?;
+    }
+
+    let comm_r = read_comm_r(&cache_dir)?;
+    let post_config = window_post_config(sector_size, api_version)?;
+
+    let start = Instant::now();
+    let mut private_replicas = BTreeMap::new();
+    private_replicas.insert(
+        sector_id,
+        PrivateReplicaInfo::new(replica_path.clone(), comm_r, cache_dir.clone())?,
+    );
+    let proof = generate_window_post(&post_config, &SEED, &private_replicas, PROVER_ID)?;
+    let elapsed = start.elapsed();
+    info!("window-post: generate-post took {:?}", elapsed);
+    report.record_phase("generate-post", elapsed);
+
+    let start = Instant::now();
+    let mut public_replicas = BTreeMap::new();
+    public_replicas.insert(sector_id, PublicReplicaInfo::new(comm_r)?);
+    let valid = verify_window_post(&post_config, &SEED, &public_replicas, PROVER_ID, &proof)?;
+    let elapsed = start.elapsed();
+    info!("window-post: verify-post took {:?}", elapsed);
+    report.record_phase("verify-post", elapsed);
+    report.pass &= valid;
+    report.proof_bytes = proof.len();
+
+    if !preserve_cache {
+        fs::remove_dir_all(&cache_dir)?;
+    }
+
+    report.finish();
+    Ok(report)
+}
+
+/// Runs (or, if a valid checkpoint for `stage` already exists and it's
+/// before `resume_point`, restores) a single replication stage, returning
+/// how long that took. The checkpoint payload is a full snapshot of
+/// `cache_dir`'s files at the moment the stage finished, not just the
+/// stage's own summary struct, so restoring it reproduces every on-disk
+/// artifact — including ones a live run of an earlier stage wrote that the
+/// *next* stage reads directly from `cache_dir` (e.g. `piece-infos.json`)
+/// — not only the ones this stage itself happens to touch.
+fn run_stage(
+    stage: Stage,
+    resume_point: Option<Stage>,
+    checkpoint: Option<&CheckpointStore>,
+    cache_dir: &Path,
+    phase: impl FnOnce() -> Result<()>,
+) -> Result<std::time::Duration> {
+    if let Some(store) = checkpoint {
+        if checkpoint::should_skip(stage, resume_point) {
+            if let Some(snapshot) = store.read_chunk(stage)? {
+                let start = Instant::now();
+                info!("window-post: restoring {:?} from checkpoint", stage);
+                restore_dir(cache_dir, &snapshot)?;
+                return Ok(start.elapsed());
+            }
+        }
+    }
+
+    let start = Instant::now();
+    phase()?;
+    let elapsed = start.elapsed();
+
+    if let Some(store) = checkpoint {
+        store.write_chunk(stage, &snapshot_dir(cache_dir)?)?;
+    }
+
+    Ok(elapsed)
+}
+
+/// Snapshots every file directly inside `dir` into one buffer: a
+/// length-prefixed filename followed by length-prefixed contents, repeated
+/// per entry. `CheckpointStore` compresses and hashes the result like any
+/// other chunk payload; `restore_dir` is the inverse.
+fn snapshot_dir(dir: &Path) -> Result<Vec<u8>> {
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut buf = Vec::new();
+    for entry in entries {
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name();
+        let name = name.to_str().expect("cache_dir entries are valid UTF-8");
+        let contents = fs::read(entry.path())?;
+
+        buf.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        buf.extend_from_slice(name.as_bytes());
+        buf.extend_from_slice(&(contents.len() as u64).to_le_bytes());
+        buf.extend_from_slice(&contents);
+    }
+    Ok(buf)
+}
+
+/// Restores a `snapshot_dir` payload into `dir`, overwriting any files of
+/// the same name already there.
+fn restore_dir(dir: &Path, snapshot: &[u8]) -> Result<()> {
+    let mut cursor = snapshot;
+    while !cursor.is_empty() {
+        if cursor.len() < 4 {
+            bail!("window-post: truncated checkpoint snapshot");
+        }
+        let (name_len, rest) = cursor.split_at(4);
+        let name_len = u32::from_le_bytes(name_len.try_into().expect("checked length")) as usize;
+
+        if rest.len() < name_len + 8 {
+            bail!("window-post: truncated checkpoint snapshot");
+        }
+        let (name, rest) = rest.split_at(name_len);
+        let name = std::str::from_utf8(name)?;
+        let (len, rest) = rest.split_at(8);
+        let len = u64::from_le_bytes(len.try_into().expect("checked length")) as usize;
+
+        if rest.len() < len {
+            bail!("window-post: truncated checkpoint snapshot");
+        }
+        let (contents, rest) = rest.split_at(len);
+
+        fs::write(dir.join(name), contents)?;
+        cursor = rest;
+    }
+    Ok(())
+}
+
+fn precommit_phase1(
+    porep_config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    staged_path: &Path,
+    replica_path: &Path,
+    sector_id: SectorId,
+) -> Result<()> {
+    let piece_size = UnpaddedBytesAmount::from(filecoin_proofs::types::PaddedBytesAmount(
+        porep_config.sector_size.0,
+    ));
+    let source = vec![0u8; u64::from(piece_size) as usize];
+    let mut staged_file = fs::File::create(staged_path)?;
+    let (piece_info, _) = add_piece(source.as_slice(), &mut staged_file, piece_size, &[])?;
+    let piece_infos = vec![piece_info];
+
+    let phase1_output = with_shape!(
+        porep_config.sector_size.0,
+        run_precommit_phase1,
+        porep_config,
+        cache_dir,
+        staged_path,
+        replica_path,
+        sector_id,
+        &piece_infos
+    )?;
+
+    fs::write(cache_dir.join("piece-infos.json"), serde_json::to_vec(&piece_infos)?)?;
+    fs::write(cache_dir.join("p1-output.json"), serde_json::to_vec(&phase1_output)?)?;
+    Ok(())
+}
+
+fn run_precommit_phase1<Tree: 'static + MerkleTreeTrait>(
+    porep_config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    staged_path: &Path,
+    replica_path: &Path,
+    sector_id: SectorId,
+    piece_infos: &[PieceInfo],
+) -> Result<filecoin_proofs::SealPreCommitPhase1Output> {
+    seal_pre_commit_phase1::<_, _, _, Tree>(
+        porep_config,
+        cache_dir,
+        staged_path,
+        replica_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        piece_infos,
+    )
+    .map_err(Into::into)
+}
+
+fn precommit_phase2(sector_size: usize, cache_dir: &Path, replica_path: &Path) -> Result<()> {
+    let phase1_output: filecoin_proofs::SealPreCommitPhase1Output =
+        serde_json::from_slice(&fs::read(cache_dir.join("p1-output.json"))?)?;
+
+    let precommit = with_shape!(
+        sector_size as u64,
+        run_precommit_phase2,
+        phase1_output,
+        cache_dir,
+        replica_path
+    )?;
+
+    fs::write(cache_dir.join("p_aux"), [precommit.comm_d, precommit.comm_r].concat())?;
+    fs::write(cache_dir.join("precommit-output.json"), serde_json::to_vec(&precommit)?)?;
+    Ok(())
+}
+
+fn run_precommit_phase2<Tree: 'static + MerkleTreeTrait>(
+    phase1_output: filecoin_proofs::SealPreCommitPhase1Output,
+    cache_dir: &Path,
+    replica_path: &Path,
+) -> Result<filecoin_proofs::SealPreCommitOutput> {
+    seal_pre_commit_phase2::<_, _, Tree>(phase1_output, cache_dir, replica_path).map_err(Into::into)
+}
+
+fn commit_phase1(
+    sector_size: usize,
+    porep_config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    replica_path: &Path,
+    sector_id: SectorId,
+) -> Result<()> {
+    let precommit: filecoin_proofs::SealPreCommitOutput =
+        serde_json::from_slice(&fs::read(cache_dir.join("precommit-output.json"))?)?;
+    let piece_infos: Vec<PieceInfo> = serde_json::from_slice(&fs::read(cache_dir.join("piece-infos.json"))?)?;
+
+    let phase1_output = with_shape!(
+        sector_size as u64,
+        run_commit_phase1,
+        porep_config,
+        cache_dir,
+        replica_path,
+        sector_id,
+        precommit,
+        &piece_infos
+    )?;
+
+    fs::write(cache_dir.join("c1-output.json"), serde_json::to_vec(&phase1_output)?)?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_commit_phase1<Tree: 'static + MerkleTreeTrait>(
+    porep_config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    replica_path: &Path,
+    sector_id: SectorId,
+    precommit: filecoin_proofs::SealPreCommitOutput,
+    piece_infos: &[PieceInfo],
+) -> Result<filecoin_proofs::SealCommitPhase1Output> {
+    seal_commit_phase1::<_, Tree>(
+        porep_config,
+        cache_dir,
+        replica_path,
+        PROVER_ID,
+        sector_id,
+        TICKET,
+        SEED,
+        precommit,
+        piece_infos,
+    )
+    .map_err(Into::into)
+}
+
+fn commit_phase2(
+    sector_size: usize,
+    porep_config: filecoin_proofs::types::PoRepConfig,
+    cache_dir: &Path,
+    sector_id: SectorId,
+) -> Result<()> {
+    let phase1_output: filecoin_proofs::SealCommitPhase1Output =
+        serde_json::from_slice(&fs::read(cache_dir.join("c1-output.json"))?)?;
+
+    let commit = with_shape!(sector_size as u64, run_commit_phase2, porep_config, phase1_output, sector_id)?;
+
+    fs::write(cache_dir.join("commit-phase2-output"), &commit.proof)?;
+    Ok(())
+}
+
+fn run_commit_phase2<Tree: 'static + MerkleTreeTrait>(
+    porep_config: filecoin_proofs::types::PoRepConfig,
+    phase1_output: filecoin_proofs::SealCommitPhase1Output,
+    sector_id: SectorId,
+) -> Result<filecoin_proofs::SealCommitOutput> {
+    seal_commit_phase2::<Tree>(porep_config, phase1_output, PROVER_ID, sector_id).map_err(Into::into)
+}
+
+fn read_comm_r(cache_dir: &Path) -> Result<[u8; 32]> {
+    let raw = fs::read(cache_dir.join("p_aux"))?;
+    if raw.len() != 64 {
+        bail!("window-post: p_aux has unexpected length {} (expected 64)", raw.len());
+    }
+    let mut comm_r = [0u8; 32];
+    comm_r.copy_from_slice(&raw[32..]);
+    Ok(comm_r)
+}
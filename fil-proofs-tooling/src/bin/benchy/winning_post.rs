@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::Instant;
+
+use anyhow::Result;
+use filecoin_proofs::types::{PrivateReplicaInfo, PublicReplicaInfo};
+use filecoin_proofs::{generate_winning_post, verify_winning_post};
+use log::info;
+use storage_proofs_core::api_version::ApiVersion;
+
+use crate::metrics::BenchReport;
+use crate::shared::{replicate, sector_id, winning_post_config, PROVER_ID, SEED};
+
+/// Benchmarks a single round of Winning PoST for a sector of `sector_size`
+/// bytes: seals the sector, then generates and verifies a Winning PoST
+/// proof over it, logging how long each step took.
+pub fn run(sector_size: usize, api_version: ApiVersion) -> Result<BenchReport> {
+    info!(
+        "winning-post: sector_size={} api_version={}",
+        sector_size, api_version
+    );
+
+    let mut report = BenchReport::new("winning-post", sector_size, api_version.to_string());
+    let sector_id = sector_id(0);
+
+    let seal_start = Instant::now();
+    let sector = replicate(sector_size, api_version, sector_id)?;
+    info!("winning-post: replication took {:?}", seal_start.elapsed());
+    report.record_phase("replication", seal_start.elapsed());
+
+    let post_config = winning_post_config(sector_size, api_version)?;
+
+    let gen_start = Instant::now();
+    let mut private_replicas = BTreeMap::new();
+    private_replicas.insert(
+        sector.sector_id,
+        PrivateReplicaInfo::new(sector.replica_path.clone(), sector.comm_r, sector.cache_dir.clone())?,
+    );
+    let proof = generate_winning_post(&post_config, &SEED, &private_replicas, PROVER_ID)?;
+    let gen_elapsed = gen_start.elapsed();
+    info!("winning-post: proof generation took {:?}", gen_elapsed);
+    report.record_phase("proof-generation", gen_elapsed);
+    report.proof_bytes = proof.len();
+
+    let verify_start = Instant::now();
+    let mut public_replicas = BTreeMap::new();
+    public_replicas.insert(sector.sector_id, PublicReplicaInfo::new(sector.comm_r)?);
+    let valid = verify_winning_post(&post_config, &SEED, &public_replicas, PROVER_ID, &proof)?;
+    let verify_elapsed = verify_start.elapsed();
+    info!(
+        "winning-post: verification took {:?} (valid={})",
+        verify_elapsed, valid
+    );
+    report.record_phase("verification", verify_elapsed);
+    report.pass = valid;
+
+    fs::remove_dir_all(&sector.cache_dir)?;
+
+    report.finish();
+    Ok(report)
+}